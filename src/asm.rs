@@ -0,0 +1,157 @@
+//! A minimal assembler turning do-core1 mnemonics into machine words.
+
+use crate::instruction::{OpCode, IMM_OFFSET, OP0_OFFSET, OP1_OFFSET};
+use crate::{Error, MAX_REGISTER_INDEX};
+
+/// Assembles `src`, one instruction per line, into machine words.
+///
+/// Supported syntax mirrors [`crate::instruction::Instruction::disassemble`]:
+/// `MNEMONIC RX, RY` for register-register instructions, `MNEMONIC RX, IMM`
+/// for register-immediate ones, `JMP IMM`, and `HLT` with no operands.
+/// Blank lines and `;` comments are ignored. On failure, the line number of
+/// the offending line is reported through [`Error::Asm`].
+pub fn assemble(src: &str) -> Result<Vec<u32>, Error> {
+    src.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line = line.split(';').next().unwrap().trim();
+            if line.is_empty() {
+                return None;
+            }
+
+            Some(assemble_line(line).map_err(|message| Error::Asm(index + 1, message)))
+        })
+        .collect()
+}
+
+fn parse_register(token: &str) -> Result<u32, String> {
+    let index = token
+        .strip_prefix('R')
+        .ok_or_else(|| format!("expected a register, got '{}'", token))?
+        .parse::<u32>()
+        .map_err(|_| format!("invalid register '{}'", token))?;
+
+    if index > MAX_REGISTER_INDEX {
+        return Err(format!(
+            "register index {} exceeds MAX_REGISTER_INDEX ({})",
+            index, MAX_REGISTER_INDEX
+        ));
+    }
+
+    Ok(index)
+}
+
+/// The immediate field is 21 bits wide, sign included.
+const IMM_MIN: i32 = -(1 << 20);
+const IMM_MAX: i32 = (1 << 20) - 1;
+
+fn parse_immediate(token: &str) -> Result<i32, String> {
+    let imm = token
+        .parse::<i32>()
+        .map_err(|_| format!("invalid immediate '{}'", token))?;
+
+    if !(IMM_MIN..=IMM_MAX).contains(&imm) {
+        return Err(format!(
+            "immediate {} out of range ({}..={})",
+            imm, IMM_MIN, IMM_MAX
+        ));
+    }
+
+    Ok(imm)
+}
+
+fn assemble_line(line: &str) -> Result<u32, String> {
+    let mut tokens = line
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty());
+
+    let mnemonic = tokens.next().ok_or("missing instruction")?;
+    let opcode =
+        OpCode::from_mnemonic(mnemonic).ok_or_else(|| format!("unknown instruction '{}'", mnemonic))?;
+    let operands: Vec<&str> = tokens.collect();
+
+    let word = match opcode {
+        OpCode::HLT => match operands.as_slice() {
+            [] => opcode.encode(),
+            _ => return Err(format!("{} takes no operands", mnemonic)),
+        },
+        OpCode::ADD | OpCode::XOR | OpCode::LDW | OpCode::STW => match operands.as_slice() {
+            [op0, op1] => {
+                let op0 = parse_register(op0)?;
+                let op1 = parse_register(op1)?;
+                opcode.encode() | (op0 << OP0_OFFSET) | (op1 << OP1_OFFSET)
+            }
+            _ => return Err(format!("{} takes two register operands", mnemonic)),
+        },
+        OpCode::JMP => match operands.as_slice() {
+            [imm] => {
+                let imm = parse_immediate(imm)?;
+                opcode.encode() | ((imm as u32) << IMM_OFFSET)
+            }
+            _ => return Err(format!("{} takes a single immediate operand", mnemonic)),
+        },
+        OpCode::LDI | OpCode::ADDI | OpCode::BEQ => match operands.as_slice() {
+            [reg, imm] => {
+                let reg = parse_register(reg)?;
+                let imm = parse_immediate(imm)?;
+                opcode.encode() | (reg << OP0_OFFSET) | ((imm as u32) << IMM_OFFSET)
+            }
+            _ => return Err(format!("{} takes a register and an immediate", mnemonic)),
+        },
+    };
+
+    Ok(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::Instruction;
+
+    #[test]
+    fn test_assemble_add_r1_r3() {
+        let words = assemble("ADD R1, R3").unwrap();
+        assert_eq!(words.len(), 1);
+
+        let insn = Instruction::disassemble(words[0]).unwrap();
+        assert_eq!(insn.opcode, OpCode::ADD);
+        assert_eq!(insn.op0, 1);
+        assert_eq!(insn.op1, 3);
+    }
+
+    #[test]
+    fn test_assemble_ignores_blank_lines_and_comments() {
+        let words = assemble("; a comment\n\nHLT\n").unwrap();
+        assert_eq!(words.len(), 1);
+        assert_eq!(words[0], OpCode::HLT.encode());
+    }
+
+    #[test]
+    fn test_assemble_unknown_mnemonic_reports_line_number() {
+        let err = assemble("HLT\nFOO R0, R1").unwrap_err();
+        match err {
+            Error::Asm(line, _) => assert_eq!(line, 2),
+            _ => panic!("expected Error::Asm"),
+        }
+    }
+
+    #[test]
+    fn test_assemble_rejects_out_of_range_register() {
+        assert!(assemble("ADD R9, R0").is_err());
+    }
+
+    #[test]
+    fn test_assemble_rejects_out_of_range_immediate() {
+        assert!(assemble("ADDI R3, 2000000").is_err());
+    }
+
+    #[test]
+    fn test_assemble_roundtrips_ldi_negative_immediate() {
+        let words = assemble("LDI R2, -1").unwrap();
+        let insn = Instruction::disassemble(words[0]).unwrap();
+
+        assert_eq!(insn.opcode, OpCode::LDI);
+        assert_eq!(insn.op0, 2);
+        assert_eq!(insn.imm, Some(-1));
+    }
+}
@@ -0,0 +1,279 @@
+use crate::{Error, MAX_REGISTER_INDEX};
+
+// Register-register (R-type) instructions are packed into the low 16 bits
+// of a u32: a 6 bit opcode field, followed by two 5 bit register operand
+// fields.
+//
+// Register-immediate (I-type) instructions share the opcode and first
+// register fields, but replace the second register with a 21 bit
+// sign-extended immediate spanning the rest of the word:
+//
+//   31                  11 10      6 5      0
+//  +----------------------+--------+--------+
+//  |         imm           |   rd   | opcode |
+//  +----------------------+--------+--------+
+const OPCODE_MASK: u32 = 0x3f;
+pub(crate) const OP0_OFFSET: u32 = 6;
+const OP0_MASK: u32 = 0x1f;
+pub(crate) const OP1_OFFSET: u32 = 11;
+const OP1_MASK: u32 = 0x1f;
+pub(crate) const IMM_OFFSET: u32 = 11;
+
+#[derive(Debug, PartialEq)]
+pub enum OpCode {
+    LDW,
+    STW,
+    ADD,
+    XOR,
+    /// Stops program execution.
+    HLT,
+    /// Loads a sign-extended immediate into a register.
+    LDI,
+    /// Adds a sign-extended immediate to a register.
+    ADDI,
+    /// Adds a sign-extended immediate to the program counter.
+    JMP,
+    /// Adds a sign-extended immediate to the program counter if a register
+    /// is zero.
+    BEQ,
+}
+
+impl OpCode {
+    fn from_u32(value: u32) -> Result<Self, Error> {
+        match value {
+            0 => Ok(OpCode::LDW),
+            1 => Ok(OpCode::STW),
+            2 => Ok(OpCode::ADD),
+            3 => Ok(OpCode::XOR),
+            4 => Ok(OpCode::HLT),
+            5 => Ok(OpCode::LDI),
+            6 => Ok(OpCode::ADDI),
+            7 => Ok(OpCode::JMP),
+            8 => Ok(OpCode::BEQ),
+            _ => Err(Error::InvalidOpCode(value)),
+        }
+    }
+
+    /// Whether this opcode uses the I-type (register, immediate) operand
+    /// layout rather than the R-type (register, register) one.
+    fn is_itype(&self) -> bool {
+        matches!(self, OpCode::LDI | OpCode::ADDI | OpCode::JMP | OpCode::BEQ)
+    }
+
+    /// The raw opcode field value for this mnemonic, for use by the
+    /// assembler.
+    pub(crate) fn encode(&self) -> u32 {
+        match self {
+            OpCode::LDW => 0,
+            OpCode::STW => 1,
+            OpCode::ADD => 2,
+            OpCode::XOR => 3,
+            OpCode::HLT => 4,
+            OpCode::LDI => 5,
+            OpCode::ADDI => 6,
+            OpCode::JMP => 7,
+            OpCode::BEQ => 8,
+        }
+    }
+
+    /// Parses an assembly mnemonic (e.g. `"ADD"`) into its [`OpCode`].
+    pub(crate) fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        match mnemonic {
+            "LDW" => Some(OpCode::LDW),
+            "STW" => Some(OpCode::STW),
+            "ADD" => Some(OpCode::ADD),
+            "XOR" => Some(OpCode::XOR),
+            "HLT" => Some(OpCode::HLT),
+            "LDI" => Some(OpCode::LDI),
+            "ADDI" => Some(OpCode::ADDI),
+            "JMP" => Some(OpCode::JMP),
+            "BEQ" => Some(OpCode::BEQ),
+            _ => None,
+        }
+    }
+
+    /// The assembly mnemonic for this opcode.
+    fn mnemonic(&self) -> &'static str {
+        match self {
+            OpCode::LDW => "LDW",
+            OpCode::STW => "STW",
+            OpCode::ADD => "ADD",
+            OpCode::XOR => "XOR",
+            OpCode::HLT => "HLT",
+            OpCode::LDI => "LDI",
+            OpCode::ADDI => "ADDI",
+            OpCode::JMP => "JMP",
+            OpCode::BEQ => "BEQ",
+        }
+    }
+}
+
+/// Whether an operand is read, written, or both by the instruction it
+/// belongs to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+/// What kind of value an [`Operand`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OperandKind {
+    Register(u32),
+    Immediate(i32),
+    /// A memory location addressed through a register.
+    MemoryViaRegister(u32),
+}
+
+/// A decoded instruction operand: what it refers to, and how the
+/// instruction uses it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Operand {
+    pub kind: OperandKind,
+    pub access: Access,
+}
+
+#[derive(Debug)]
+pub struct Instruction {
+    pub opcode: OpCode,
+    pub op0: u32,
+    pub op1: u32,
+    /// The sign-extended immediate, for I-type instructions.
+    pub imm: Option<i32>,
+}
+
+impl Instruction {
+    /// Decodes a raw instruction word into an [`Instruction`].
+    pub fn disassemble(insn: u32) -> Result<Self, Error> {
+        let opcode = OpCode::from_u32(insn & OPCODE_MASK)?;
+        let op0 = (insn >> OP0_OFFSET) & OP0_MASK;
+
+        if opcode.is_itype() {
+            // JMP has no register operand; every other I-type opcode
+            // decodes op0 as its destination/condition register.
+            let op0 = if opcode == OpCode::JMP { 0 } else { op0 };
+            if op0 > MAX_REGISTER_INDEX {
+                return Err(Error::InvalidRegister(op0));
+            }
+
+            let imm = (insn as i32) >> IMM_OFFSET;
+
+            return Ok(Instruction {
+                opcode,
+                op0,
+                op1: 0,
+                imm: Some(imm),
+            });
+        }
+
+        if op0 > MAX_REGISTER_INDEX {
+            return Err(Error::InvalidRegister(op0));
+        }
+
+        let op1 = (insn >> OP1_OFFSET) & OP1_MASK;
+        if op1 > MAX_REGISTER_INDEX {
+            return Err(Error::InvalidRegister(op1));
+        }
+
+        Ok(Instruction {
+            opcode,
+            op0,
+            op1,
+            imm: None,
+        })
+    }
+
+    /// Describes this instruction's operands: what each one refers to
+    /// (register, immediate, or memory through a register), and whether it
+    /// is read, written, or both.
+    pub fn operands(&self) -> Vec<Operand> {
+        use Access::*;
+        use OperandKind::*;
+
+        match self.opcode {
+            OpCode::ADD | OpCode::XOR => vec![
+                Operand {
+                    kind: Register(self.op0),
+                    access: ReadWrite,
+                },
+                Operand {
+                    kind: Register(self.op1),
+                    access: Read,
+                },
+            ],
+            OpCode::LDW => vec![
+                Operand {
+                    kind: Register(self.op0),
+                    access: Write,
+                },
+                Operand {
+                    kind: MemoryViaRegister(self.op1),
+                    access: Read,
+                },
+            ],
+            OpCode::STW => vec![
+                Operand {
+                    kind: Register(self.op0),
+                    access: Read,
+                },
+                Operand {
+                    kind: MemoryViaRegister(self.op1),
+                    access: Write,
+                },
+            ],
+            OpCode::LDI => vec![
+                Operand {
+                    kind: Register(self.op0),
+                    access: Write,
+                },
+                Operand {
+                    kind: Immediate(self.imm.unwrap()),
+                    access: Read,
+                },
+            ],
+            OpCode::ADDI => vec![
+                Operand {
+                    kind: Register(self.op0),
+                    access: ReadWrite,
+                },
+                Operand {
+                    kind: Immediate(self.imm.unwrap()),
+                    access: Read,
+                },
+            ],
+            OpCode::JMP => vec![Operand {
+                kind: Immediate(self.imm.unwrap()),
+                access: Read,
+            }],
+            OpCode::BEQ => vec![
+                Operand {
+                    kind: Register(self.op0),
+                    access: Read,
+                },
+                Operand {
+                    kind: Immediate(self.imm.unwrap()),
+                    access: Read,
+                },
+            ],
+            OpCode::HLT => vec![],
+        }
+    }
+}
+
+impl std::fmt::Display for Instruction {
+    /// Renders the canonical textual form of this instruction, e.g.
+    /// `"ADD R1, R3"` or `"LDI R2, 5"`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.opcode.mnemonic())?;
+
+        match self.opcode {
+            OpCode::HLT => Ok(()),
+            OpCode::JMP => write!(f, " {}", self.imm.unwrap()),
+            OpCode::LDI | OpCode::ADDI | OpCode::BEQ => {
+                write!(f, " R{}, {}", self.op0, self.imm.unwrap())
+            }
+            _ => write!(f, " R{}, R{}", self.op0, self.op1),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+pub mod asm;
+pub mod instruction;
+pub mod machine;
+pub mod memory;
+
+/// Highest valid register index. do-core1 exposes 8 general-purpose
+/// registers, R0 through R7.
+pub const MAX_REGISTER_INDEX: u32 = 7;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The opcode field did not match any known instruction.
+    InvalidOpCode(u32),
+    /// A register operand was greater than `MAX_REGISTER_INDEX`.
+    InvalidRegister(u32),
+    /// No device is mapped at this address, or the access straddles a
+    /// device's boundary.
+    BadAddress(u32),
+    /// A program binary or assembly source file could not be read.
+    Io(std::io::Error),
+    /// An assembly source line failed to parse, with its 1-based line
+    /// number and a description of the problem.
+    Asm(usize, String),
+}
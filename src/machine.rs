@@ -0,0 +1,265 @@
+use crate::instruction::{Instruction, OpCode};
+use crate::memory::AddressSpace;
+use crate::MAX_REGISTER_INDEX;
+
+/// All do-core1 instructions are one word wide.
+const INSTRUCTION_WIDTH: u32 = 4;
+
+fn add(op0: u32, op1: u32) -> Option<u32> {
+    op0.checked_add(op1)
+}
+
+fn xor(op0: u32, op1: u32) -> u32 {
+    op0 ^ op1
+}
+
+/// The cause of a trapped CPU exception, do-core1's `mcause` values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExceptionType {
+    /// The opcode field did not match any known instruction, a register
+    /// operand was out of range, or no instruction could be fetched at `pc`.
+    IllegalInstruction,
+    /// An `ADD` overflowed.
+    AdditionOverflow,
+    /// An `LDW` addressed a device that isn't mapped, or straddled one.
+    LoadAccessFault,
+    /// An `STW` addressed a device that isn't mapped, or straddled one.
+    StoreAccessFault,
+}
+
+/// A do-core1 CPU: its registers, the address space it is wired to, a
+/// program counter stepping through a loaded program, and its trap state.
+pub struct Machine {
+    pub registers: [u32; MAX_REGISTER_INDEX as usize + 1],
+    pub address_space: AddressSpace,
+    pub pc: u32,
+    halted: bool,
+    /// Address instructions are vectored to on an exception (do-core1's
+    /// `mtvec`). `None` means no handler is installed, so an exception
+    /// halts the machine instead.
+    pub mtvec: Option<u32>,
+    /// Cause of the most recently raised exception.
+    pub mcause: Option<ExceptionType>,
+    /// Value of `pc` at the time of the most recently raised exception.
+    pub mepc: u32,
+}
+
+impl Machine {
+    pub fn new(address_space: AddressSpace) -> Self {
+        Machine {
+            registers: [0; MAX_REGISTER_INDEX as usize + 1],
+            address_space,
+            pc: 0,
+            halted: false,
+            mtvec: None,
+            mcause: None,
+            mepc: 0,
+        }
+    }
+
+    /// Whether the machine has executed a `HLT` instruction, or halted on
+    /// an unhandled exception.
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Fetches the instruction at `pc`, executes it, and advances `pc` (or
+    /// redirects it, for branches). Faults raise an exception instead of
+    /// unwinding: see [`Machine::handle_exception`].
+    pub fn step(&mut self) {
+        let faulting_pc = self.pc;
+
+        let word = match self.address_space.read_word(self.pc) {
+            Ok(word) => word,
+            Err(_) => return self.handle_exception(ExceptionType::IllegalInstruction, faulting_pc),
+        };
+
+        let decoded_instruction = match Instruction::disassemble(word) {
+            Ok(decoded_instruction) => decoded_instruction,
+            Err(_) => return self.handle_exception(ExceptionType::IllegalInstruction, faulting_pc),
+        };
+
+        let op0 = decoded_instruction.op0 as usize;
+        let op1 = decoded_instruction.op1 as usize;
+        let mut next_pc = self.pc.wrapping_add(INSTRUCTION_WIDTH);
+
+        let outcome = match decoded_instruction.opcode {
+            OpCode::ADD => match add(self.registers[op0], self.registers[op1]) {
+                Some(value) => {
+                    self.registers[op0] = value;
+                    Ok(())
+                }
+                None => Err(ExceptionType::AdditionOverflow),
+            },
+            OpCode::XOR => {
+                self.registers[op0] = xor(self.registers[op0], self.registers[op1]);
+                Ok(())
+            }
+            OpCode::LDW => match self.address_space.read_word(self.registers[op1]) {
+                Ok(value) => {
+                    self.registers[op0] = value;
+                    Ok(())
+                }
+                Err(_) => Err(ExceptionType::LoadAccessFault),
+            },
+            OpCode::STW => self
+                .address_space
+                .write_word(self.registers[op1], self.registers[op0])
+                .map_err(|_| ExceptionType::StoreAccessFault),
+            OpCode::LDI => {
+                self.registers[op0] = decoded_instruction.imm.unwrap() as u32;
+                Ok(())
+            }
+            OpCode::ADDI => {
+                self.registers[op0] =
+                    (self.registers[op0] as i32).wrapping_add(decoded_instruction.imm.unwrap())
+                        as u32;
+                Ok(())
+            }
+            OpCode::JMP => {
+                next_pc = (self.pc as i32).wrapping_add(decoded_instruction.imm.unwrap()) as u32;
+                Ok(())
+            }
+            OpCode::BEQ => {
+                if self.registers[op0] == 0 {
+                    next_pc =
+                        (self.pc as i32).wrapping_add(decoded_instruction.imm.unwrap()) as u32;
+                }
+                Ok(())
+            }
+            OpCode::HLT => {
+                self.halted = true;
+                Ok(())
+            }
+        };
+
+        match outcome {
+            Ok(()) => self.pc = next_pc,
+            Err(exception) => self.handle_exception(exception, faulting_pc),
+        }
+    }
+
+    /// Records `exception` as the cause of a trap taken at `faulting_pc`,
+    /// then either vectors execution to the configured trap handler
+    /// ([`Machine::mtvec`]) or halts with a diagnostic dump if none is
+    /// installed.
+    pub fn handle_exception(&mut self, exception: ExceptionType, faulting_pc: u32) {
+        self.mcause = Some(exception);
+        self.mepc = faulting_pc;
+
+        match self.mtvec {
+            Some(handler) => self.pc = handler,
+            None => {
+                self.halted = true;
+                eprintln!(
+                    "do-core1: unhandled exception {:?} at pc {:#x}",
+                    exception, faulting_pc
+                );
+                for (index, register) in self.registers.iter().enumerate() {
+                    eprintln!("\tR{}: {:#x?}", index, *register);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::Ram;
+
+    const CODE_BASE: u32 = 0x0;
+    const CODE_SIZE: u32 = 0x1000;
+    const DATA_BASE: u32 = 0x1000;
+    const DATA_SIZE: u32 = 0x1000;
+
+    fn machine_with_program(words: &[u32]) -> Machine {
+        let mut address_space = AddressSpace::new();
+        address_space.map(Box::new(Ram::new(CODE_BASE, CODE_SIZE)));
+        address_space.map(Box::new(Ram::new(DATA_BASE, DATA_SIZE)));
+
+        for (index, word) in words.iter().enumerate() {
+            address_space
+                .write_word(CODE_BASE + (index as u32) * 4, *word)
+                .unwrap();
+        }
+
+        Machine::new(address_space)
+    }
+
+    #[test]
+    fn test_step_hlt_halts_the_machine() {
+        let mut machine = machine_with_program(&[OpCode::HLT.encode()]);
+        assert!(!machine.halted());
+
+        machine.step();
+
+        assert!(machine.halted());
+    }
+
+    #[test]
+    fn test_step_advances_pc_by_one_instruction() {
+        let mut machine = machine_with_program(&[OpCode::XOR.encode(), OpCode::HLT.encode()]);
+
+        machine.step();
+
+        assert_eq!(machine.pc, 4);
+        assert!(!machine.halted());
+    }
+
+    #[test]
+    fn test_step_runs_a_loaded_program_to_completion() {
+        // LDI R0, 5 ; STW R0, R1 ; LDW R2, R1 ; HLT
+        let ldi = OpCode::LDI.encode() | (5u32 << crate::instruction::IMM_OFFSET);
+        let stw = OpCode::STW.encode() | (1 << crate::instruction::OP1_OFFSET);
+        let ldw = OpCode::LDW.encode()
+            | (2 << crate::instruction::OP0_OFFSET)
+            | (1 << crate::instruction::OP1_OFFSET);
+        let hlt = OpCode::HLT.encode();
+
+        let mut machine = machine_with_program(&[ldi, stw, ldw, hlt]);
+        machine.registers[1] = DATA_BASE;
+
+        while !machine.halted() {
+            machine.step();
+        }
+
+        assert_eq!(machine.registers[2], 5);
+    }
+
+    #[test]
+    fn test_step_illegal_instruction_halts_without_trap_handler() {
+        let mut machine = machine_with_program(&[0x3f]);
+
+        machine.step();
+
+        assert!(machine.halted());
+        assert_eq!(machine.mcause, Some(ExceptionType::IllegalInstruction));
+        assert_eq!(machine.mepc, 0);
+    }
+
+    #[test]
+    fn test_step_addition_overflow_traps() {
+        let mut machine = machine_with_program(&[OpCode::ADD.encode()]);
+        machine.registers[0] = u32::MAX;
+        machine.registers[1] = 1;
+
+        machine.step();
+
+        assert!(machine.halted());
+        assert_eq!(machine.mcause, Some(ExceptionType::AdditionOverflow));
+    }
+
+    #[test]
+    fn test_handle_exception_vectors_to_trap_handler_instead_of_halting() {
+        let mut machine = machine_with_program(&[0x3f]);
+        machine.mtvec = Some(0x100);
+
+        machine.step();
+
+        assert!(!machine.halted());
+        assert_eq!(machine.pc, 0x100);
+        assert_eq!(machine.mcause, Some(ExceptionType::IllegalInstruction));
+        assert_eq!(machine.mepc, 0);
+    }
+}
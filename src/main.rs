@@ -1,22 +1,47 @@
 use clap::Parser;
-use do_core::instruction::{Instruction, OpCode};
+use do_core::instruction::Instruction;
+use do_core::machine::Machine;
+use do_core::memory::{AddressSpace, Ram};
 use do_core::{Error, MAX_REGISTER_INDEX};
 
+/// Base address and size of the RAM region backing single instruction mode.
+const RAM_BASE: u32 = 0x0;
+const RAM_SIZE: u32 = 0x1000;
+
+/// Base address and size of the code region a loaded program is placed into.
+const CODE_BASE: u32 = 0x0;
+const CODE_SIZE: u32 = 0x1000;
+
+/// Base address and size of the data region available to a loaded program.
+const DATA_BASE: u32 = 0x1000;
+const DATA_SIZE: u32 = 0x1000;
+
 #[derive(Parser)]
 #[clap(version, author)]
 struct DoCoreOpts {
-    /// DO Core instruction
+    /// A single DO Core instruction to execute, e.g. `0x1842`.
+    #[clap(short, long, conflicts_with_all = &["program", "asm"])]
+    insn: Option<String>,
+
+    /// Path to a flat binary holding little-endian u32 instruction words to
+    /// load and run.
+    #[clap(short, long, conflicts_with = "asm")]
+    program: Option<String>,
+
+    /// Path to a do-core1 assembly source file to assemble and run.
     #[clap(short, long)]
-    insn: String,
-}
+    asm: Option<String>,
 
-fn add(op0: u32, op1: u32) -> Result<u32, Error> {
-    op0.checked_add(op1)
-        .ok_or(Error::AdditionOverflow(op0, op1))
+    /// Address exceptions are vectored to, e.g. `0x100`. Without one, an
+    /// exception halts the machine with a diagnostic dump.
+    #[clap(short, long)]
+    trap_handler: Option<String>,
 }
 
-fn xor(op0: u32, op1: u32) -> u32 {
-    op0 ^ op1
+fn parse_trap_handler(trap_handler: &Option<String>) -> Option<u32> {
+    trap_handler
+        .as_deref()
+        .map(|addr| u32::from_str_radix(addr.trim_start_matches("0x"), 16).unwrap())
 }
 
 fn dump_cpu_state(preamble: &str, registers: &[u32; MAX_REGISTER_INDEX as usize + 1]) {
@@ -26,41 +51,101 @@ fn dump_cpu_state(preamble: &str, registers: &[u32; MAX_REGISTER_INDEX as usize
     }
 }
 
-fn main() -> Result<(), Error> {
-    let opts: DoCoreOpts = DoCoreOpts::parse();
-    let insn = u32::from_str_radix(opts.insn.trim_start_matches("0x"), 16).unwrap();
-    let mut registers = [0u32; MAX_REGISTER_INDEX as usize + 1];
+/// Decodes and executes a single instruction word against an otherwise
+/// empty machine.
+fn run_insn(insn: &str, trap_handler: Option<u32>) -> Result<(), Error> {
+    let insn = u32::from_str_radix(insn.trim_start_matches("0x"), 16).unwrap();
+
+    // Decoding here is only for the printout below; an invalid instruction
+    // is not fatal; `Machine::step` will raise the same trap either way.
+    if let Ok(decoded_instruction) = Instruction::disassemble(insn) {
+        println!(
+            "do-core-1: instruction decoded into {:?}",
+            decoded_instruction
+        );
+    }
+
+    let mut address_space = AddressSpace::new();
+    address_space.map(Box::new(Ram::new(RAM_BASE, RAM_SIZE)));
+    address_space.write_word(RAM_BASE, insn)?;
+
+    let mut machine = Machine::new(address_space);
+    machine.mtvec = trap_handler;
     // Arbitrary initial registers value.
     // Registers will eventually be initialized through memory loads.
-    for (index, register) in registers.iter_mut().enumerate() {
+    for (index, register) in machine.registers.iter_mut().enumerate() {
         *register = index as u32 * 0x10;
     }
 
-    dump_cpu_state("Initial CPU state", &registers);
+    dump_cpu_state("Initial CPU state", &machine.registers);
+    machine.step();
+    dump_cpu_state("Final CPU state", &machine.registers);
 
-    let decoded_instruction = Instruction::disassemble(insn)?;
-    println!(
-        "do-core-1: instruction decoded into {:?}",
-        decoded_instruction
-    );
-    let op0 = decoded_instruction.op0 as usize;
-    let op1 = decoded_instruction.op1 as usize;
+    Ok(())
+}
 
-    match decoded_instruction.opcode {
-        OpCode::ADD => registers[op0] = add(registers[op0], registers[op1])?,
-        OpCode::XOR => registers[op0] = xor(registers[op0], registers[op1]),
+/// Loads `words` into a fresh machine's code region and runs it to
+/// completion, i.e. until a `HLT` is executed or it halts on an unhandled
+/// exception.
+fn run_words(words: &[u32], trap_handler: Option<u32>) -> Result<(), Error> {
+    let mut address_space = AddressSpace::new();
+    address_space.map(Box::new(Ram::new(CODE_BASE, CODE_SIZE)));
+    address_space.map(Box::new(Ram::new(DATA_BASE, DATA_SIZE)));
 
-        _ => panic!("Unknown opcode {:?}", decoded_instruction.opcode),
+    for (index, word) in words.iter().enumerate() {
+        address_space.write_word(CODE_BASE + (index as u32) * 4, *word)?;
     }
 
-    dump_cpu_state("Final CPU state", &registers);
+    let mut machine = Machine::new(address_space);
+    machine.mtvec = trap_handler;
+
+    dump_cpu_state("Initial CPU state", &machine.registers);
+    while !machine.halted() {
+        machine.step();
+    }
+    dump_cpu_state("Final CPU state", &machine.registers);
 
     Ok(())
 }
 
+/// Loads a flat binary of little-endian u32 words and runs it.
+fn run_program(path: &str, trap_handler: Option<u32>) -> Result<(), Error> {
+    let program = std::fs::read(path).map_err(Error::Io)?;
+    let words: Vec<u32> = program
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+        .collect();
+
+    run_words(&words, trap_handler)
+}
+
+/// Assembles a do-core1 source file and runs it.
+fn run_asm(path: &str, trap_handler: Option<u32>) -> Result<(), Error> {
+    let src = std::fs::read_to_string(path).map_err(Error::Io)?;
+    let words = do_core::asm::assemble(&src)?;
+
+    run_words(&words, trap_handler)
+}
+
+fn main() -> Result<(), Error> {
+    let opts: DoCoreOpts = DoCoreOpts::parse();
+    let trap_handler = parse_trap_handler(&opts.trap_handler);
+
+    match (opts.insn, opts.program, opts.asm) {
+        (Some(insn), None, None) => run_insn(&insn, trap_handler),
+        (None, Some(program), None) => run_program(&program, trap_handler),
+        (None, None, Some(asm)) => run_asm(&asm, trap_handler),
+        _ => {
+            eprintln!("do-core1: pass exactly one of --insn, --program or --asm");
+            std::process::exit(1);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{Error, Instruction, OpCode};
+    use crate::{Error, Instruction};
+    use do_core::instruction::OpCode;
 
     #[test]
     fn test_instruction_disassemble_add_r1_r3() -> Result<(), Error> {
@@ -137,4 +222,107 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_instruction_disassemble_ldi_r2_5() -> Result<(), Error> {
+        let insn_bytes: u32 = 0x2885;
+        let insn = Instruction::disassemble(insn_bytes)?;
+
+        assert_eq!(insn.opcode, OpCode::LDI);
+        assert_eq!(insn.op0, 2);
+        assert_eq!(insn.imm, Some(5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_instruction_disassemble_addi_r3_neg1() -> Result<(), Error> {
+        let insn_bytes: u32 = 0xfffff8c6;
+        let insn = Instruction::disassemble(insn_bytes)?;
+
+        assert_eq!(insn.opcode, OpCode::ADDI);
+        assert_eq!(insn.op0, 3);
+        assert_eq!(insn.imm, Some(-1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_instruction_disassemble_jmp_plus8() -> Result<(), Error> {
+        let insn_bytes: u32 = 0x4007;
+        let insn = Instruction::disassemble(insn_bytes)?;
+
+        assert_eq!(insn.opcode, OpCode::JMP);
+        assert_eq!(insn.imm, Some(8));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_instruction_disassemble_beq_r1_neg4() -> Result<(), Error> {
+        let insn_bytes: u32 = 0xffffe048;
+        let insn = Instruction::disassemble(insn_bytes)?;
+
+        assert_eq!(insn.opcode, OpCode::BEQ);
+        assert_eq!(insn.op0, 1);
+        assert_eq!(insn.imm, Some(-4));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_instruction_operands_add_r1_r3() -> Result<(), Error> {
+        use do_core::instruction::{Access, Operand, OperandKind};
+
+        let insn = Instruction::disassemble(0x1842)?;
+
+        assert_eq!(
+            insn.operands(),
+            vec![
+                Operand {
+                    kind: OperandKind::Register(1),
+                    access: Access::ReadWrite,
+                },
+                Operand {
+                    kind: OperandKind::Register(3),
+                    access: Access::Read,
+                },
+            ]
+        );
+        assert_eq!(insn.to_string(), "ADD R1, R3");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_instruction_operands_stw_r5_r0() -> Result<(), Error> {
+        use do_core::instruction::{Access, Operand, OperandKind};
+
+        let insn = Instruction::disassemble(0x0141)?;
+
+        assert_eq!(
+            insn.operands(),
+            vec![
+                Operand {
+                    kind: OperandKind::Register(5),
+                    access: Access::Read,
+                },
+                Operand {
+                    kind: OperandKind::MemoryViaRegister(0),
+                    access: Access::Write,
+                },
+            ]
+        );
+        assert_eq!(insn.to_string(), "STW R5, R0");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_instruction_display_ldi_r2_5() -> Result<(), Error> {
+        let insn = Instruction::disassemble(0x2885)?;
+        assert_eq!(insn.to_string(), "LDI R2, 5");
+
+        Ok(())
+    }
 }
@@ -0,0 +1,217 @@
+//! A small memory-mapped bus.
+//!
+//! Devices advertise where they live in the address space through
+//! [`Addressable`], and serve accesses through [`Readable`]/[`Writable`]. An
+//! [`AddressSpace`] owns a set of mapped devices and routes each access to
+//! whichever one claims the address.
+
+use crate::Error;
+
+/// A device that can be mapped into an [`AddressSpace`].
+pub trait Addressable {
+    /// First address this device answers to.
+    fn base(&self) -> u32;
+    /// Number of bytes this device occupies.
+    fn len(&self) -> u32;
+
+    /// Whether this device occupies no address space at all.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A device that can be read from.
+pub trait Readable: Addressable {
+    fn read_byte(&self, offset: u32) -> Result<u8, Error>;
+
+    fn read_halfword(&self, offset: u32) -> Result<u16, Error> {
+        let lo = self.read_byte(offset)? as u16;
+        let hi = self.read_byte(offset + 1)? as u16;
+        Ok(lo | (hi << 8))
+    }
+
+    fn read_word(&self, offset: u32) -> Result<u32, Error> {
+        let lo = self.read_halfword(offset)? as u32;
+        let hi = self.read_halfword(offset + 2)? as u32;
+        Ok(lo | (hi << 16))
+    }
+}
+
+/// A device that can be written to.
+pub trait Writable: Addressable {
+    fn write_byte(&mut self, offset: u32, value: u8) -> Result<(), Error>;
+
+    fn write_halfword(&mut self, offset: u32, value: u16) -> Result<(), Error> {
+        self.write_byte(offset, value as u8)?;
+        self.write_byte(offset + 1, (value >> 8) as u8)
+    }
+
+    fn write_word(&mut self, offset: u32, value: u32) -> Result<(), Error> {
+        self.write_halfword(offset, value as u16)?;
+        self.write_halfword(offset + 2, (value >> 16) as u16)
+    }
+}
+
+/// A device that is both readable and writable, i.e. mappable into an
+/// [`AddressSpace`].
+pub trait Device: Readable + Writable {}
+impl<T: Readable + Writable> Device for T {}
+
+/// A flat block of RAM, backed by a `Vec<u8>`.
+pub struct Ram {
+    base: u32,
+    data: Vec<u8>,
+}
+
+impl Ram {
+    pub fn new(base: u32, len: u32) -> Self {
+        Ram {
+            base,
+            data: vec![0; len as usize],
+        }
+    }
+}
+
+impl Addressable for Ram {
+    fn base(&self) -> u32 {
+        self.base
+    }
+
+    fn len(&self) -> u32 {
+        self.data.len() as u32
+    }
+}
+
+impl Readable for Ram {
+    fn read_byte(&self, offset: u32) -> Result<u8, Error> {
+        self.data
+            .get(offset as usize)
+            .copied()
+            .ok_or(Error::BadAddress(self.base + offset))
+    }
+}
+
+impl Writable for Ram {
+    fn write_byte(&mut self, offset: u32, value: u8) -> Result<(), Error> {
+        let base = self.base;
+        match self.data.get_mut(offset as usize) {
+            Some(byte) => {
+                *byte = value;
+                Ok(())
+            }
+            None => Err(Error::BadAddress(base + offset)),
+        }
+    }
+}
+
+/// A memory-mapped bus: a set of devices, each claiming its own slice of the
+/// address space.
+pub struct AddressSpace {
+    regions: Vec<Box<dyn Device>>,
+}
+
+impl AddressSpace {
+    pub fn new() -> Self {
+        AddressSpace {
+            regions: Vec::new(),
+        }
+    }
+
+    /// Maps `device` into the address space. Regions are kept sorted by base
+    /// address so lookups can walk them in address order.
+    pub fn map(&mut self, device: Box<dyn Device>) {
+        self.regions.push(device);
+        self.regions.sort_by_key(|device| device.base());
+    }
+
+    /// Finds the region containing `addr` and able to serve an access of
+    /// `access_len` bytes without straddling its end, returning its index
+    /// and the device-local offset.
+    fn locate(&self, addr: u32, access_len: u32) -> Result<(usize, u32), Error> {
+        self.regions
+            .iter()
+            .position(|device| {
+                addr.checked_sub(device.base())
+                    .and_then(|offset| offset.checked_add(access_len))
+                    .is_some_and(|end| end <= device.len())
+            })
+            .map(|index| (index, addr - self.regions[index].base()))
+            .ok_or(Error::BadAddress(addr))
+    }
+
+    pub fn read_word(&self, addr: u32) -> Result<u32, Error> {
+        let (index, offset) = self.locate(addr, 4)?;
+        self.regions[index].read_word(offset)
+    }
+
+    pub fn write_word(&mut self, addr: u32, value: u32) -> Result<(), Error> {
+        let (index, offset) = self.locate(addr, 4)?;
+        self.regions[index].write_word(offset, value)
+    }
+}
+
+impl Default for AddressSpace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ram_read_write_word_roundtrip() {
+        let mut ram = Ram::new(0x1000, 0x100);
+        ram.write_word(0x10, 0xdeadbeef).unwrap();
+        assert_eq!(ram.read_word(0x10).unwrap(), 0xdeadbeef);
+    }
+
+    #[test]
+    fn test_ram_out_of_bounds_access_is_bad_address() {
+        let ram = Ram::new(0x1000, 0x10);
+        assert!(matches!(
+            ram.read_byte(0x10),
+            Err(Error::BadAddress(0x1010))
+        ));
+    }
+
+    #[test]
+    fn test_address_space_dispatches_to_mapped_region() {
+        let mut space = AddressSpace::new();
+        space.map(Box::new(Ram::new(0x0, 0x10)));
+        space.map(Box::new(Ram::new(0x1000, 0x10)));
+
+        space.write_word(0x1004, 0x42).unwrap();
+        assert_eq!(space.read_word(0x1004).unwrap(), 0x42);
+    }
+
+    #[test]
+    fn test_address_space_unmapped_address_is_bad_address() {
+        let space = AddressSpace::new();
+        assert!(matches!(space.read_word(0x0), Err(Error::BadAddress(0x0))));
+    }
+
+    #[test]
+    fn test_address_space_straddling_access_is_bad_address() {
+        let mut space = AddressSpace::new();
+        space.map(Box::new(Ram::new(0x0, 0x10)));
+
+        // A word read starting three bytes before the region's end straddles it.
+        assert!(matches!(
+            space.read_word(0xd),
+            Err(Error::BadAddress(0xd))
+        ));
+    }
+
+    #[test]
+    fn test_address_space_near_u32_max_does_not_panic() {
+        let mut space = AddressSpace::new();
+        space.map(Box::new(Ram::new(0x0, 0x10)));
+
+        assert!(matches!(
+            space.read_word(u32::MAX - 1),
+            Err(Error::BadAddress(_))
+        ));
+    }
+}